@@ -0,0 +1,58 @@
+//! Google service account credentials, loaded from the JSON file downloadable
+//! from the Firebase console.
+
+use crate::errors::{FirebaseError, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// The service account JSON as handed out by the Firebase / Google Cloud console.
+///
+/// This is what you need to create a [`crate::sessions::service_account::Session`] or
+/// to bootstrap a [`crate::sessions::user::Session`].
+#[derive(Debug, Clone, Deserialize)]
+pub struct Credentials {
+    pub project_id: String,
+    pub private_key_id: String,
+    pub private_key: String,
+    pub client_email: String,
+    /// Web API key, used to call the Identity Toolkit / securetoken REST
+    /// endpoints on behalf of end users. Not present in a plain service
+    /// account JSON; set it separately if you need [`crate::sessions::user`].
+    #[serde(default)]
+    pub api_key: Option<String>,
+    #[serde(skip)]
+    pub(crate) public_keys: HashMap<String, String>,
+}
+
+impl Credentials {
+    /// Read the service account credentials from a JSON file on disk.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        Self::from_str(&content)
+    }
+
+    /// Parse the service account credentials from a JSON string.
+    pub fn from_str(content: &str) -> Result<Self> {
+        let mut cred: Credentials = serde_json::from_str(content)?;
+        // The public keys used to verify ID tokens are fetched separately from
+        // Google; for the service account's own signing key we already have
+        // what we need in `private_key_id` / `private_key`.
+        cred.public_keys
+            .insert(cred.private_key_id.clone(), cred.private_key.clone());
+        Ok(cred)
+    }
+
+    /// Look up the public key belonging to the given key id, if known.
+    pub fn public_key(&self, kid: &str) -> Option<&str> {
+        self.public_keys.get(kid).map(|s| s.as_str())
+    }
+}
+
+impl std::convert::TryFrom<&str> for Credentials {
+    type Error = FirebaseError;
+
+    fn try_from(value: &str) -> Result<Self> {
+        Credentials::from_str(value)
+    }
+}
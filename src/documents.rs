@@ -0,0 +1,537 @@
+//! Reading, writing and querying Firestore documents.
+//!
+//! All functions take a `&mut impl FirebaseAuthBearer` so they work
+//! identically with a [`crate::sessions::service_account::Session`] or a
+//! [`crate::sessions::user::Session`] (or any custom implementation).
+
+pub mod collection;
+pub mod listen;
+
+use crate::dto;
+use crate::errors::{FirebaseError, Result};
+use crate::firebase_rest_to_rust::{document_to_pod, pod_to_fields};
+use crate::FirebaseAuthBearer;
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+pub use collection::{Collection, Reference};
+pub use listen::{listen, ChangeEvent, ChangeStream, ListenTarget};
+
+const BASE_URL: &str = "https://firestore.googleapis.com/v1";
+
+fn documents_url(project_id: &str, collection: &str) -> String {
+    format!(
+        "{}/projects/{}/databases/(default)/documents/{}",
+        BASE_URL, project_id, collection
+    )
+}
+
+fn document_url(project_id: &str, path: &str) -> String {
+    format!(
+        "{}/projects/{}/databases/(default)/documents/{}",
+        BASE_URL, project_id, path
+    )
+}
+
+/// Metadata about a document that was just written.
+#[derive(Debug, Clone)]
+pub struct WriteResult {
+    pub document_id: String,
+    pub create_time: Option<DateTime<Utc>>,
+    pub update_time: Option<DateTime<Utc>>,
+}
+
+fn write_result_from_document(document_id: String, doc: &dto::Document) -> WriteResult {
+    WriteResult {
+        document_id,
+        create_time: doc
+            .create_time
+            .as_deref()
+            .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+            .map(|t| t.with_timezone(&Utc)),
+        update_time: doc
+            .update_time
+            .as_deref()
+            .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+            .map(|t| t.with_timezone(&Utc)),
+    }
+}
+
+fn check_status(resp: reqwest::blocking::Response, context: &str) -> Result<reqwest::blocking::Response> {
+    check_status_with_precondition(resp, context, false)
+}
+
+/// The part of a Firestore/Google API error body this crate cares about. See
+/// <https://cloud.google.com/apis/design/errors> for the full envelope.
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+struct ErrorBody {
+    error: ErrorDetail,
+}
+
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+struct ErrorDetail {
+    #[serde(default)]
+    status: String,
+}
+
+/// Like [`check_status`], but a response whose body's `error.status` is
+/// `FAILED_PRECONDITION` or `ABORTED` is reported as
+/// [`FirebaseError::PreconditionFailed`] when the request carried a
+/// `currentDocument` precondition, since that's Firestore's way of signalling
+/// a violated one. A 400/409 for any other reason (a malformed request, an
+/// auth failure, ...) is still reported as a plain [`FirebaseError::APIError`].
+fn check_status_with_precondition(
+    resp: reqwest::blocking::Response,
+    context: &str,
+    had_precondition: bool,
+) -> Result<reqwest::blocking::Response> {
+    let status = resp.status();
+    if status.is_success() {
+        return Ok(resp);
+    }
+    let body = resp.text().unwrap_or_default();
+    if had_precondition
+        && (status == reqwest::StatusCode::BAD_REQUEST || status == reqwest::StatusCode::CONFLICT)
+        && is_precondition_violation(&body)
+    {
+        return Err(FirebaseError::PreconditionFailed(body));
+    }
+    Err(FirebaseError::APIError(
+        status.as_u16(),
+        body,
+        context.to_owned(),
+    ))
+}
+
+/// Whether a Firestore error body's `error.status` denotes a violated
+/// `currentDocument` precondition.
+fn is_precondition_violation(body: &str) -> bool {
+    match serde_json::from_str::<ErrorBody>(body) {
+        Ok(parsed) => matches!(parsed.error.status.as_str(), "FAILED_PRECONDITION" | "ABORTED"),
+        Err(_) => false,
+    }
+}
+
+/// An optimistic-concurrency precondition for [`write`] and [`delete`],
+/// mapped onto Firestore's `currentDocument` precondition.
+#[derive(Debug, Clone)]
+pub enum Precondition {
+    /// Require that the document currently exists (`true`), or that it
+    /// currently does not (`false`) — e.g. "create only if absent".
+    Exists(bool),
+    /// Require that the document's current `update_time` matches exactly,
+    /// for a compare-and-swap write against a previously read document.
+    UpdateTime(DateTime<Utc>),
+}
+
+impl Precondition {
+    fn query_param(&self) -> (&'static str, String) {
+        match self {
+            Precondition::Exists(exists) => ("currentDocument.exists", exists.to_string()),
+            Precondition::UpdateTime(time) => (
+                "currentDocument.updateTime",
+                time.to_rfc3339_opts(chrono::SecondsFormat::Nanos, true),
+            ),
+        }
+    }
+
+    fn apply_to_url(precondition: &Option<Precondition>, url: &mut String) {
+        if let Some(precondition) = precondition {
+            let (key, value) = precondition.query_param();
+            let separator = if url.contains('?') { '&' } else { '?' };
+            url.push(separator);
+            url.push_str(key);
+            url.push('=');
+            url.push_str(&value);
+        }
+    }
+}
+
+/// Write (fully overwrite) a document. If `document_id` is `None`, Firestore
+/// assigns a new, random document id. `precondition` lets a caller express
+/// "create only if absent" or a compare-and-swap against a previously read
+/// `update_time`; a violated precondition fails with
+/// [`FirebaseError::PreconditionFailed`].
+pub fn write<T, A>(
+    auth: &mut A,
+    collection: &str,
+    document_id: Option<&str>,
+    obj: &T,
+    precondition: Option<Precondition>,
+) -> Result<WriteResult>
+where
+    T: Serialize,
+    for<'b> A: FirebaseAuthBearer<'b>,
+{
+    let fields = pod_to_fields(obj)?;
+    let document = dto::Document {
+        fields,
+        ..Default::default()
+    };
+    let client = reqwest::blocking::Client::new();
+    let bearer = auth.bearer();
+    let project_id = auth.projectid().to_owned();
+
+    let resp = match document_id {
+        Some(id) => {
+            let mut url = document_url(&project_id, &format!("{}/{}", collection, id));
+            Precondition::apply_to_url(&precondition, &mut url);
+            client
+                .patch(&url)
+                .bearer_auth(&bearer)
+                .json(&document)
+                .send()?
+        }
+        None => {
+            let mut url = documents_url(&project_id, collection);
+            Precondition::apply_to_url(&precondition, &mut url);
+            client
+                .post(&url)
+                .bearer_auth(&bearer)
+                .json(&document)
+                .send()?
+        }
+    };
+    let resp = check_status_with_precondition(resp, "documents::write", precondition.is_some())?;
+    let doc: dto::Document = resp.json()?;
+    let document_id = document_id
+        .map(|s| s.to_owned())
+        .unwrap_or_else(|| doc.name.rsplit('/').next().unwrap_or_default().to_owned());
+    Ok(write_result_from_document(document_id, &doc))
+}
+
+/// Read a single document and deserialize it into `T`.
+pub fn read<T, A>(auth: &mut A, collection: &str, document_id: &str) -> Result<T>
+where
+    T: DeserializeOwned,
+    for<'b> A: FirebaseAuthBearer<'b>,
+{
+    let url = document_url(
+        &auth.projectid().to_owned(),
+        &format!("{}/{}", collection, document_id),
+    );
+    let resp = reqwest::blocking::Client::new()
+        .get(&url)
+        .bearer_auth(auth.bearer())
+        .send()?;
+    let resp = check_status(resp, "documents::read")?;
+    let doc: dto::Document = resp.json()?;
+    document_to_pod(&doc)
+}
+
+/// Query a collection for documents where `field` compares to `value` using
+/// `operator`.
+pub fn query<T, A>(
+    auth: &mut A,
+    collection: &str,
+    value: &str,
+    operator: dto::FieldOperator,
+    field: &str,
+) -> Result<Vec<T>>
+where
+    T: DeserializeOwned,
+    for<'b> A: FirebaseAuthBearer<'b>,
+{
+    let url = format!(
+        "{}/projects/{}/databases/(default)/documents:runQuery",
+        BASE_URL,
+        auth.projectid()
+    );
+    let body = serde_json::json!({
+        "structuredQuery": {
+            "from": [{ "collectionId": collection }],
+            "where": {
+                "fieldFilter": {
+                    "field": { "fieldPath": field },
+                    "op": operator.as_str(),
+                    "value": { "stringValue": value },
+                }
+            }
+        }
+    });
+    let resp = reqwest::blocking::Client::new()
+        .post(&url)
+        .bearer_auth(auth.bearer())
+        .json(&body)
+        .send()?;
+    let resp = check_status(resp, "documents::query")?;
+    let items: Vec<dto::RunQueryResponseItem> = resp.json()?;
+    items
+        .into_iter()
+        .filter_map(|item| item.document)
+        .map(|doc| document_to_pod(&doc))
+        .collect()
+}
+
+/// Lazily iterate over every document in `collection`, one REST page at a time.
+pub fn list<'a, T, A>(auth: &'a mut A, collection: &str) -> List<'a, T, A>
+where
+    for<'b> A: FirebaseAuthBearer<'b>,
+{
+    List {
+        auth,
+        collection: collection.to_owned(),
+        buffer: Vec::new().into_iter(),
+        page_token: None,
+        done: false,
+        _marker: std::marker::PhantomData,
+    }
+}
+
+/// Iterator returned by [`list`]. Transparently fetches the next page of
+/// results from Firestore once the current one is exhausted.
+///
+/// Iteration ends early, without reporting an error, at the first page
+/// request that fails or the first document that does not deserialize into `T`.
+pub struct List<'a, T, A> {
+    auth: &'a mut A,
+    collection: String,
+    buffer: std::vec::IntoIter<dto::Document>,
+    page_token: Option<String>,
+    done: bool,
+    _marker: std::marker::PhantomData<T>,
+}
+
+impl<'a, T, A> Iterator for List<'a, T, A>
+where
+    T: DeserializeOwned,
+    for<'b> A: FirebaseAuthBearer<'b>,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        loop {
+            if let Some(doc) = self.buffer.next() {
+                return document_to_pod(&doc).ok();
+            }
+            if self.done {
+                return None;
+            }
+            let mut url = documents_url(self.auth.projectid(), &self.collection);
+            if let Some(token) = &self.page_token {
+                url = format!("{}?pageToken={}", url, token);
+            }
+            let resp = reqwest::blocking::Client::new()
+                .get(&url)
+                .bearer_auth(self.auth.bearer())
+                .send()
+                .ok()?;
+            let page: dto::ListDocumentsResponse = resp.json().ok()?;
+            self.page_token = page.next_page_token;
+            self.done = self.page_token.is_none();
+            self.buffer = page.documents.into_iter();
+            if self.buffer.len() == 0 && self.done {
+                return None;
+            }
+        }
+    }
+}
+
+/// Delete the document at `path` (`"collection/document_id"`).
+///
+/// If `fail_on_not_found` is `true`, a missing document results in an error
+/// instead of being treated as a no-op. `precondition` lets a caller require
+/// e.g. "delete only if it still has the `update_time` I last read", failing
+/// with [`FirebaseError::PreconditionFailed`] otherwise.
+pub fn delete<A>(
+    auth: &mut A,
+    path: &str,
+    fail_on_not_found: bool,
+    precondition: Option<Precondition>,
+) -> Result<()>
+where
+    for<'b> A: FirebaseAuthBearer<'b>,
+{
+    let mut url = document_url(auth.projectid(), path);
+    Precondition::apply_to_url(&precondition, &mut url);
+    let resp = reqwest::blocking::Client::new()
+        .delete(&url)
+        .bearer_auth(auth.bearer())
+        .send()?;
+    if resp.status() == reqwest::StatusCode::NOT_FOUND && !fail_on_not_found {
+        return Ok(());
+    }
+    check_status_with_precondition(resp, "documents::delete", precondition.is_some())?;
+    Ok(())
+}
+
+/// Append an `updateMask.fieldPaths` query parameter per field name to
+/// `base_url`, sorted for deterministic output.
+fn update_mask_url<'a>(base_url: &str, field_paths: impl Iterator<Item = &'a str>) -> String {
+    let mut field_paths: Vec<&str> = field_paths.collect();
+    field_paths.sort_unstable();
+
+    let mut url = base_url.to_owned();
+    url.push('?');
+    for field_path in &field_paths {
+        url.push_str("updateMask.fieldPaths=");
+        url.push_str(field_path);
+        url.push('&');
+    }
+    url.pop();
+    url
+}
+
+/// Partially update a document, preserving any fields not present on `obj`.
+///
+/// `obj` is typically a DTO that only carries the fields you want to change;
+/// Firestore's `updateMask.fieldPaths` is set to exactly those field names so
+/// everything else on the stored document is left untouched.
+pub fn update<T, A>(
+    auth: &mut A,
+    collection: &str,
+    document_id: &str,
+    obj: &T,
+) -> Result<WriteResult>
+where
+    T: Serialize,
+    for<'b> A: FirebaseAuthBearer<'b>,
+{
+    let fields = pod_to_fields(obj)?;
+    if fields.is_empty() {
+        // Without a mask Firestore would replace the whole document.
+        return Err(FirebaseError::Generic(
+            "documents::update needs at least one field to update".to_owned(),
+        ));
+    }
+    let base_url = document_url(auth.projectid(), &format!("{}/{}", collection, document_id));
+    let url = update_mask_url(&base_url, fields.keys().map(|k| k.as_str()));
+
+    let document = dto::Document {
+        fields,
+        ..Default::default()
+    };
+
+    let resp = reqwest::blocking::Client::new()
+        .patch(&url)
+        .bearer_auth(auth.bearer())
+        .json(&document)
+        .send()?;
+    let resp = check_status(resp, "documents::update")?;
+    let doc: dto::Document = resp.json()?;
+    Ok(write_result_from_document(document_id.to_owned(), &doc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn update_mask_url_sorts_and_joins_field_paths() {
+        let url = update_mask_url(
+            "https://example.com/doc",
+            vec!["b", "a", "c"].into_iter(),
+        );
+        assert_eq!(
+            url,
+            "https://example.com/doc?updateMask.fieldPaths=a&updateMask.fieldPaths=b&updateMask.fieldPaths=c"
+        );
+    }
+
+    #[test]
+    fn update_mask_url_single_field_path_has_no_trailing_ampersand() {
+        let url = update_mask_url("https://example.com/doc", vec!["a"].into_iter());
+        assert_eq!(url, "https://example.com/doc?updateMask.fieldPaths=a");
+    }
+
+    #[test]
+    fn update_mask_url_with_no_fields_leaves_base_url_untouched() {
+        let url = update_mask_url("https://example.com/doc", std::iter::empty());
+        assert_eq!(url, "https://example.com/doc");
+    }
+
+    #[test]
+    fn pod_to_fields_round_trips_through_document_to_pod() {
+        #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+        struct DemoDTO {
+            a_string: String,
+            an_int: i64,
+            nested: Vec<String>,
+        }
+
+        let original = DemoDTO {
+            a_string: "abc".to_owned(),
+            an_int: 42,
+            nested: vec!["x".to_owned(), "y".to_owned()],
+        };
+
+        let fields = crate::firebase_rest_to_rust::pod_to_fields(&original).unwrap();
+        let doc = dto::Document {
+            fields,
+            ..Default::default()
+        };
+        let round_tripped: DemoDTO = crate::firebase_rest_to_rust::document_to_pod(&doc).unwrap();
+
+        assert_eq!(round_tripped, original);
+    }
+
+    #[test]
+    fn precondition_violation_detected_from_error_status() {
+        let body = r#"{"error":{"code":400,"message":"...","status":"FAILED_PRECONDITION"}}"#;
+        assert!(is_precondition_violation(body));
+
+        let body = r#"{"error":{"code":409,"message":"...","status":"ABORTED"}}"#;
+        assert!(is_precondition_violation(body));
+    }
+
+    #[test]
+    fn unrelated_error_status_is_not_a_precondition_violation() {
+        let body = r#"{"error":{"code":400,"message":"...","status":"INVALID_ARGUMENT"}}"#;
+        assert!(!is_precondition_violation(body));
+
+        let body = r#"{"error":{"code":401,"message":"...","status":"UNAUTHENTICATED"}}"#;
+        assert!(!is_precondition_violation(body));
+    }
+
+    #[test]
+    fn unparseable_body_is_not_a_precondition_violation() {
+        assert!(!is_precondition_violation("not json"));
+        assert!(!is_precondition_violation(""));
+    }
+
+    #[test]
+    fn exists_precondition_query_param() {
+        let (key, value) = Precondition::Exists(true).query_param();
+        assert_eq!(key, "currentDocument.exists");
+        assert_eq!(value, "true");
+
+        let (key, value) = Precondition::Exists(false).query_param();
+        assert_eq!(key, "currentDocument.exists");
+        assert_eq!(value, "false");
+    }
+
+    #[test]
+    fn update_time_precondition_query_param() {
+        let time = DateTime::parse_from_rfc3339("2024-01-02T03:04:05.678Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let (key, value) = Precondition::UpdateTime(time).query_param();
+        assert_eq!(key, "currentDocument.updateTime");
+        assert_eq!(value, "2024-01-02T03:04:05.678000000Z");
+    }
+
+    #[test]
+    fn apply_to_url_no_precondition_leaves_url_untouched() {
+        let mut url = "https://example.com/doc".to_owned();
+        Precondition::apply_to_url(&None, &mut url);
+        assert_eq!(url, "https://example.com/doc");
+    }
+
+    #[test]
+    fn apply_to_url_appends_query_param_with_question_mark() {
+        let mut url = "https://example.com/doc".to_owned();
+        Precondition::apply_to_url(&Some(Precondition::Exists(true)), &mut url);
+        assert_eq!(url, "https://example.com/doc?currentDocument.exists=true");
+    }
+
+    #[test]
+    fn apply_to_url_appends_query_param_with_ampersand_if_url_already_has_one() {
+        let mut url = "https://example.com/doc?foo=bar".to_owned();
+        Precondition::apply_to_url(&Some(Precondition::Exists(false)), &mut url);
+        assert_eq!(
+            url,
+            "https://example.com/doc?foo=bar&currentDocument.exists=false"
+        );
+    }
+}
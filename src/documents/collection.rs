@@ -0,0 +1,132 @@
+//! A typed object-document-mapper layer on top of the free functions in
+//! [`crate::documents`], so callers get a path-safe handle per collection
+//! instead of repeating the collection string and document id on every call.
+
+use crate::documents::{self, WriteResult};
+use crate::errors::Result;
+use crate::FirebaseAuthBearer;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::marker::PhantomData;
+
+/// A handle to a single Firestore collection, typed by the struct its
+/// documents (de)serialize into.
+pub struct Collection<'a, T, A> {
+    auth: &'a mut A,
+    path: String,
+    _marker: PhantomData<T>,
+}
+
+impl<'a, T, A> Collection<'a, T, A>
+where
+    T: Serialize + DeserializeOwned,
+    for<'b> A: FirebaseAuthBearer<'b>,
+{
+    /// Build a handle for the collection at `path`, e.g. `"users"` or, for a
+    /// sub-collection, `"users/abc/orders"`.
+    pub fn new(auth: &'a mut A, path: impl Into<String>) -> Self {
+        Collection {
+            auth,
+            path: path.into(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Create a new document with a Firestore-assigned id and return a
+    /// [`Reference`] to it.
+    pub fn create(&mut self, obj: &T) -> Result<Reference> {
+        let result = documents::write(self.auth, &self.path, None, obj, None)?;
+        Ok(Reference::new(format!("{}/{}", self.path, result.document_id)))
+    }
+
+    /// Fetch the document with the given id.
+    pub fn get(&mut self, id: &str) -> Result<T> {
+        documents::read(self.auth, &self.path, id)
+    }
+
+    /// Overwrite (or create) the document with the given id.
+    pub fn set(&mut self, id: &str, obj: &T) -> Result<WriteResult> {
+        documents::write(self.auth, &self.path, Some(id), obj, None)
+    }
+
+    /// Delete the document with the given id.
+    pub fn delete(&mut self, id: &str) -> Result<()> {
+        documents::delete(self.auth, &format!("{}/{}", self.path, id), false, None)
+    }
+
+    /// Lazily iterate over every document in this collection.
+    pub fn list(&mut self) -> documents::List<'_, T, A> {
+        documents::list(self.auth, &self.path)
+    }
+}
+
+/// A path-safe reference to a single document, returned by
+/// [`Collection::create`]. Can spawn a [`Collection`] handle for one of its
+/// sub-collections. It holds only the document path and carries no document
+/// type; the type is given per call to [`Reference::collection`].
+#[derive(Debug, Clone)]
+pub struct Reference {
+    path: String,
+}
+
+impl Reference {
+    pub(crate) fn new(path: String) -> Self {
+        Reference { path }
+    }
+
+    /// The full `parent/{id}/child/...` document path.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// The document's own id, i.e. the last path segment.
+    pub fn id(&self) -> &str {
+        self.path.rsplit('/').next().unwrap_or_default()
+    }
+
+    /// Get a typed handle to a sub-collection nested under this document.
+    pub fn collection<'a, T, A>(&self, auth: &'a mut A, name: &str) -> Collection<'a, T, A>
+    where
+        T: Serialize + DeserializeOwned,
+        for<'b> A: FirebaseAuthBearer<'b>,
+    {
+        Collection::new(auth, format!("{}/{}", self.path, name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct DummyAuth;
+
+    impl<'a> FirebaseAuthBearer<'a> for DummyAuth {
+        fn projectid(&'a self) -> &'a str {
+            "dummy-project"
+        }
+        fn bearer(&'a self) -> String {
+            "dummy-token".to_owned()
+        }
+    }
+
+    #[test]
+    fn reference_path_and_id() {
+        let reference = Reference::new("users/abc".to_owned());
+        assert_eq!(reference.path(), "users/abc");
+        assert_eq!(reference.id(), "abc");
+    }
+
+    #[test]
+    fn reference_id_with_nested_path() {
+        let reference = Reference::new("users/abc/orders/42".to_owned());
+        assert_eq!(reference.id(), "42");
+    }
+
+    #[test]
+    fn collection_composes_nested_sub_collection_path() {
+        let reference = Reference::new("users/abc".to_owned());
+        let mut auth = DummyAuth;
+        let sub: Collection<'_, (), DummyAuth> = reference.collection(&mut auth, "orders");
+        assert_eq!(sub.path, "users/abc/orders");
+    }
+}
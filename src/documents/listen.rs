@@ -0,0 +1,224 @@
+//! Watching a document or a collection query for changes via Firestore's
+//! server-streaming `Listen` RPC.
+//!
+//! The REST/JSON transport for this RPC streams its response messages as a
+//! top-level JSON array whose elements arrive incrementally (`[{...}, {...},
+//! ...]`), not as newline-delimited JSON, so [`ChangeStream`] reads the
+//! connection byte-by-byte and extracts each balanced top-level `{...}`
+//! object rather than splitting on line breaks.
+
+use crate::dto;
+use crate::errors::{FirebaseError, Result};
+use crate::firebase_rest_to_rust::document_to_pod;
+use crate::FirebaseAuthBearer;
+use serde::de::DeserializeOwned;
+use std::collections::HashSet;
+use std::io::{BufReader, Read};
+use std::marker::PhantomData;
+
+/// What to watch: a single document, or every document matching a
+/// collection-scoped query.
+#[derive(Debug, Clone)]
+pub enum ListenTarget {
+    Document(String),
+    Query { collection: String },
+}
+
+/// A typed change to a watched document.
+#[derive(Debug, Clone)]
+pub enum ChangeEvent<T> {
+    /// A document entered the watched set: it was not in it on this stream
+    /// yet, or it had been removed from it since.
+    Added(T),
+    /// A document already in the watched set changed.
+    Modified(T),
+    /// A document left the watched set, identified by its full resource name.
+    Removed(String),
+}
+
+/// Open Firestore's `Listen` stream for `target` and return an iterator of
+/// typed change events. Pass a previously observed
+/// [`ChangeStream::resume_token`] to re-establish a dropped stream without
+/// replaying the whole collection.
+pub fn listen<T, A>(
+    auth: &mut A,
+    target: ListenTarget,
+    resume_token: Option<String>,
+) -> Result<ChangeStream<T>>
+where
+    for<'b> A: FirebaseAuthBearer<'b>,
+{
+    let project_id = auth.projectid().to_owned();
+    let url = format!(
+        "{}/projects/{}/databases/(default)/documents:listen",
+        super::BASE_URL,
+        project_id
+    );
+
+    let add_target = match &target {
+        ListenTarget::Document(path) => serde_json::json!({
+            "documents": {
+                "documents": [format!(
+                    "projects/{}/databases/(default)/documents/{}",
+                    project_id, path
+                )]
+            }
+        }),
+        ListenTarget::Query { collection } => serde_json::json!({
+            "query": {
+                "structuredQuery": { "from": [{ "collectionId": collection }] }
+            }
+        }),
+    };
+    let mut add_target = add_target;
+    if let serde_json::Value::Object(map) = &mut add_target {
+        map.insert("targetId".to_owned(), serde_json::json!(1));
+        if let Some(token) = &resume_token {
+            map.insert("resumeToken".to_owned(), serde_json::json!(token));
+        }
+    }
+
+    let body = serde_json::json!({
+        "database": format!("projects/{}/databases/(default)", project_id),
+        "addTarget": add_target,
+    });
+
+    let resp = reqwest::blocking::Client::new()
+        .post(&url)
+        .bearer_auth(auth.bearer())
+        .json(&body)
+        .send()?;
+    let resp = super::check_status(resp, "documents::listen")?;
+
+    Ok(ChangeStream {
+        reader: BufReader::new(resp),
+        resume_token,
+        seen: HashSet::new(),
+        _marker: PhantomData,
+    })
+}
+
+/// Iterator over [`ChangeEvent`]s yielded by [`listen`]. Dropping it closes
+/// the underlying HTTP connection.
+pub struct ChangeStream<T> {
+    reader: BufReader<reqwest::blocking::Response>,
+    resume_token: Option<String>,
+    seen: HashSet<String>,
+    _marker: PhantomData<T>,
+}
+
+impl<T> ChangeStream<T> {
+    /// The most recently observed resume token, to hand back to [`listen`]
+    /// when re-establishing a dropped stream.
+    pub fn resume_token(&self) -> Option<&str> {
+        self.resume_token.as_deref()
+    }
+}
+
+impl<T> ChangeStream<T> {
+    /// Read bytes until a single balanced top-level `{...}` object has been
+    /// accumulated, skipping the array punctuation (`[`, `,`, `]`) and
+    /// whitespace Firestore sends between messages. Returns `Ok(None)` once
+    /// the connection is closed with no partial message pending.
+    ///
+    /// Braces inside quoted JSON strings (honoring `\"` escapes) don't count
+    /// towards the depth, so a document field containing a literal `{`/`}`
+    /// doesn't truncate the message early. Bytes are buffered raw and decoded
+    /// as UTF-8 once the whole object has been collected, so a multi-byte
+    /// codepoint split across reads is never interpreted a byte at a time.
+    fn read_next_object(&mut self) -> Result<Option<String>> {
+        let mut buf = Vec::new();
+        let mut depth = 0i32;
+        let mut in_string = false;
+        let mut escaped = false;
+        let mut byte = [0u8; 1];
+        loop {
+            match self.reader.read(&mut byte) {
+                Ok(0) if buf.is_empty() => return Ok(None),
+                Ok(0) => {
+                    return Err(FirebaseError::Generic(
+                        "Listen stream closed in the middle of a message".to_owned(),
+                    ))
+                }
+                Ok(_) => {}
+                Err(e) => return Err(FirebaseError::IO(e)),
+            }
+            let b = byte[0];
+            if depth == 0 && b != b'{' {
+                continue; // '[', ',', ']', or whitespace between messages
+            }
+
+            if in_string {
+                if escaped {
+                    escaped = false;
+                } else if b == b'\\' {
+                    escaped = true;
+                } else if b == b'"' {
+                    in_string = false;
+                }
+            } else if b == b'"' {
+                in_string = true;
+            } else if b == b'{' {
+                depth += 1;
+            } else if b == b'}' {
+                depth -= 1;
+            }
+
+            buf.push(b);
+            if depth == 0 {
+                return String::from_utf8(buf)
+                    .map(Some)
+                    .map_err(|e| FirebaseError::Generic(format!("Listen stream was not valid UTF-8: {}", e)));
+            }
+        }
+    }
+}
+
+impl<T: DeserializeOwned> Iterator for ChangeStream<T> {
+    type Item = Result<ChangeEvent<T>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let object = match self.read_next_object() {
+                Ok(Some(object)) => object,
+                Ok(None) => return None,
+                Err(e) => return Some(Err(e)),
+            };
+
+            let message: dto::ListenResponse = match serde_json::from_str(&object) {
+                Ok(message) => message,
+                Err(e) => return Some(Err(FirebaseError::JSON(e))),
+            };
+
+            match message {
+                dto::ListenResponse::TargetChange(change) => {
+                    if change.resume_token.is_some() {
+                        self.resume_token = change.resume_token;
+                    }
+                }
+                dto::ListenResponse::DocumentChange(change) => {
+                    let name = change.document.name.clone();
+                    let event = match document_to_pod(&change.document) {
+                        Ok(obj) => {
+                            if self.seen.insert(name) {
+                                ChangeEvent::Added(obj)
+                            } else {
+                                ChangeEvent::Modified(obj)
+                            }
+                        }
+                        Err(e) => return Some(Err(e)),
+                    };
+                    return Some(Ok(event));
+                }
+                dto::ListenResponse::DocumentDelete(change) => {
+                    self.seen.remove(&change.document);
+                    return Some(Ok(ChangeEvent::Removed(change.document)));
+                }
+                dto::ListenResponse::DocumentRemove(change) => {
+                    self.seen.remove(&change.document);
+                    return Some(Ok(ChangeEvent::Removed(change.document)));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,129 @@
+//! Firestore REST API wire types: the document/value representation the
+//! server actually speaks, independent of the typed Rust structs application
+//! code works with. See [`crate::firebase_rest_to_rust`] for the conversion
+//! between the two.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Comparison operator for a [`crate::documents::query`] filter.
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldOperator {
+    LESS_THAN,
+    LESS_THAN_OR_EQUAL,
+    GREATER_THAN,
+    GREATER_THAN_OR_EQUAL,
+    EQUAL,
+    ARRAY_CONTAINS,
+}
+
+impl FieldOperator {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            FieldOperator::LESS_THAN => "LESS_THAN",
+            FieldOperator::LESS_THAN_OR_EQUAL => "LESS_THAN_OR_EQUAL",
+            FieldOperator::GREATER_THAN => "GREATER_THAN",
+            FieldOperator::GREATER_THAN_OR_EQUAL => "GREATER_THAN_OR_EQUAL",
+            FieldOperator::EQUAL => "EQUAL",
+            FieldOperator::ARRAY_CONTAINS => "ARRAY_CONTAINS",
+        }
+    }
+}
+
+/// A Firestore `Document`, as returned by or sent to the REST API.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Document {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub fields: HashMap<String, Value>,
+    #[serde(default, rename = "createTime")]
+    pub create_time: Option<String>,
+    #[serde(default, rename = "updateTime")]
+    pub update_time: Option<String>,
+}
+
+/// A single Firestore field value, tagged by its wire-format variant name.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Value {
+    #[serde(rename = "nullValue")]
+    NullValue(()),
+    #[serde(rename = "booleanValue")]
+    BooleanValue(bool),
+    #[serde(rename = "integerValue")]
+    IntegerValue(String),
+    #[serde(rename = "doubleValue")]
+    DoubleValue(f64),
+    #[serde(rename = "timestampValue")]
+    TimestampValue(String),
+    #[serde(rename = "stringValue")]
+    StringValue(String),
+    #[serde(rename = "mapValue")]
+    MapValue(MapValue),
+    #[serde(rename = "arrayValue")]
+    ArrayValue(ArrayValue),
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MapValue {
+    #[serde(default)]
+    pub fields: HashMap<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ArrayValue {
+    #[serde(default)]
+    pub values: Vec<Value>,
+}
+
+/// Response envelope for `documents:runQuery`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RunQueryResponseItem {
+    pub document: Option<Document>,
+}
+
+/// Response envelope for listing a collection.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ListDocumentsResponse {
+    #[serde(default)]
+    pub documents: Vec<Document>,
+    #[serde(default, rename = "nextPageToken")]
+    pub next_page_token: Option<String>,
+}
+
+/// One message of Firestore's server-streaming `Listen` response.
+#[derive(Debug, Clone, Deserialize)]
+pub enum ListenResponse {
+    #[serde(rename = "targetChange")]
+    TargetChange(TargetChange),
+    #[serde(rename = "documentChange")]
+    DocumentChange(DocumentChangeMsg),
+    #[serde(rename = "documentDelete")]
+    DocumentDelete(DocumentDeleteMsg),
+    #[serde(rename = "documentRemove")]
+    DocumentRemove(DocumentRemoveMsg),
+}
+
+/// Carries the resume token a client should persist to re-establish a
+/// dropped `Listen` stream without replaying everything from scratch.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TargetChange {
+    #[serde(default, rename = "resumeToken")]
+    pub resume_token: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DocumentChangeMsg {
+    pub document: Document,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DocumentDeleteMsg {
+    pub document: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DocumentRemoveMsg {
+    pub document: String,
+}
@@ -0,0 +1,66 @@
+//! Error types returned by this crate.
+
+use std::fmt;
+
+/// The crate-wide result type.
+pub type Result<T> = std::result::Result<T, FirebaseError>;
+
+/// Errors that can occur while talking to the Firebase Auth and Firestore REST APIs.
+#[derive(Debug)]
+pub enum FirebaseError {
+    /// An IO error occurred, e.g. while reading the service account file.
+    IO(std::io::Error),
+    /// A JSON (de)serialization error.
+    JSON(serde_json::Error),
+    /// The underlying HTTP request failed.
+    Request(reqwest::Error),
+    /// The Firestore or Firebase Auth REST API returned a non-2xx response.
+    APIError(u16, String, String),
+    /// A `write`/`delete` `currentDocument` precondition did not hold, e.g.
+    /// the document already existed, didn't exist, or its `update_time` had
+    /// moved on since it was last read.
+    PreconditionFailed(String),
+    /// A JWT could not be generated or parsed.
+    JWT(String),
+    /// A catch-all for conversion/validation errors that don't warrant their
+    /// own variant.
+    Generic(String),
+}
+
+impl fmt::Display for FirebaseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FirebaseError::IO(e) => write!(f, "IO error: {}", e),
+            FirebaseError::JSON(e) => write!(f, "JSON error: {}", e),
+            FirebaseError::Request(e) => write!(f, "Request error: {}", e),
+            FirebaseError::APIError(code, message, context) => {
+                write!(f, "API error {}: {} ({})", code, message, context)
+            }
+            FirebaseError::PreconditionFailed(message) => {
+                write!(f, "Precondition failed: {}", message)
+            }
+            FirebaseError::JWT(e) => write!(f, "JWT error: {}", e),
+            FirebaseError::Generic(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for FirebaseError {}
+
+impl From<std::io::Error> for FirebaseError {
+    fn from(e: std::io::Error) -> Self {
+        FirebaseError::IO(e)
+    }
+}
+
+impl From<serde_json::Error> for FirebaseError {
+    fn from(e: serde_json::Error) -> Self {
+        FirebaseError::JSON(e)
+    }
+}
+
+impl From<reqwest::Error> for FirebaseError {
+    fn from(e: reqwest::Error) -> Self {
+        FirebaseError::Request(e)
+    }
+}
@@ -0,0 +1,84 @@
+//! Conversion between the Firestore REST wire format ([`crate::dto`]) and the
+//! plain Rust structs application code serializes with `serde`.
+
+use crate::dto::{ArrayValue, Document, MapValue, Value};
+use crate::errors::{FirebaseError, Result};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value as Json;
+use std::collections::HashMap;
+
+/// Serialize `obj` and turn its top-level fields into a Firestore fields map.
+pub fn pod_to_fields<T: Serialize>(obj: &T) -> Result<HashMap<String, Value>> {
+    match serde_json::to_value(obj)? {
+        Json::Object(map) => Ok(map
+            .into_iter()
+            .map(|(k, v)| (k, json_to_firebase_value(&v)))
+            .collect()),
+        _ => Err(FirebaseError::Generic(
+            "Only struct-like types can be written to a Firestore document".to_owned(),
+        )),
+    }
+}
+
+/// Turn a Firestore [`Document`] back into `T` by round-tripping through
+/// `serde_json::Value`.
+pub fn document_to_pod<T: DeserializeOwned>(doc: &Document) -> Result<T> {
+    let map: serde_json::Map<String, Json> = doc
+        .fields
+        .iter()
+        .map(|(k, v)| (k.clone(), firebase_value_to_json(v)))
+        .collect();
+    Ok(serde_json::from_value(Json::Object(map))?)
+}
+
+/// Convert a single `serde_json::Value` into its Firestore wire representation.
+pub fn json_to_firebase_value(value: &Json) -> Value {
+    match value {
+        Json::Null => Value::NullValue(()),
+        Json::Bool(b) => Value::BooleanValue(*b),
+        Json::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                Value::IntegerValue(i.to_string())
+            } else {
+                Value::DoubleValue(n.as_f64().unwrap_or_default())
+            }
+        }
+        Json::String(s) => Value::StringValue(s.clone()),
+        Json::Array(values) => Value::ArrayValue(ArrayValue {
+            values: values.iter().map(json_to_firebase_value).collect(),
+        }),
+        Json::Object(map) => Value::MapValue(MapValue {
+            fields: map
+                .iter()
+                .map(|(k, v)| (k.clone(), json_to_firebase_value(v)))
+                .collect(),
+        }),
+    }
+}
+
+/// Convert a single Firestore wire value back into a `serde_json::Value`.
+pub fn firebase_value_to_json(value: &Value) -> Json {
+    match value {
+        Value::NullValue(()) => Json::Null,
+        Value::BooleanValue(b) => Json::Bool(*b),
+        Value::IntegerValue(i) => i
+            .parse::<i64>()
+            .map(Json::from)
+            .unwrap_or(Json::String(i.clone())),
+        Value::DoubleValue(d) => serde_json::Number::from_f64(*d)
+            .map(Json::Number)
+            .unwrap_or(Json::Null),
+        Value::TimestampValue(t) => Json::String(t.clone()),
+        Value::StringValue(s) => Json::String(s.clone()),
+        Value::MapValue(map) => Json::Object(
+            map.fields
+                .iter()
+                .map(|(k, v)| (k.clone(), firebase_value_to_json(v)))
+                .collect(),
+        ),
+        Value::ArrayValue(array) => {
+            Json::Array(array.values.iter().map(firebase_value_to_json).collect())
+        }
+    }
+}
@@ -0,0 +1,61 @@
+//! Shared JWT signing helpers used by the `sessions` module.
+
+use crate::credentials::Credentials;
+use crate::errors::{FirebaseError, Result};
+use serde::Serialize;
+use std::time::SystemTime;
+
+/// Sign `claims` as a JWT using the service account's RSA private key, with
+/// `credentials.private_key_id` set as the header's `kid`.
+pub(crate) fn sign_claims<T: Serialize>(credentials: &Credentials, claims: &T) -> Result<String> {
+    use ring::rand::SystemRandom;
+    use ring::signature::{RsaKeyPair, RSA_PKCS1_SHA256};
+
+    let header = base64_json(&serde_json::json!({
+        "alg": "RS256",
+        "typ": "JWT",
+        "kid": credentials.private_key_id,
+    }))?;
+    let claims = base64_json(claims)?;
+    let unsigned = format!("{}.{}", header, claims);
+
+    let der = pem_to_der(&credentials.private_key)?;
+    let key_pair =
+        RsaKeyPair::from_pkcs8(&der).map_err(|e| FirebaseError::JWT(format!("{:?}", e)))?;
+    let mut signature = vec![0u8; key_pair.public_modulus_len()];
+    key_pair
+        .sign(
+            &RSA_PKCS1_SHA256,
+            &SystemRandom::new(),
+            unsigned.as_bytes(),
+            &mut signature,
+        )
+        .map_err(|e| FirebaseError::JWT(format!("{:?}", e)))?;
+
+    Ok(format!(
+        "{}.{}",
+        unsigned,
+        base64::encode_config(&signature, base64::URL_SAFE_NO_PAD)
+    ))
+}
+
+/// Seconds since the Unix epoch, as used in JWT `iat`/`exp` claims.
+pub(crate) fn unix_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+fn base64_json<T: Serialize>(value: &T) -> Result<String> {
+    let bytes = serde_json::to_vec(value)?;
+    Ok(base64::encode_config(&bytes, base64::URL_SAFE_NO_PAD))
+}
+
+fn pem_to_der(pem: &str) -> Result<Vec<u8>> {
+    let der = pem
+        .lines()
+        .filter(|line| !line.starts_with("-----"))
+        .collect::<String>();
+    base64::decode(&der).map_err(|e| FirebaseError::JWT(e.to_string()))
+}
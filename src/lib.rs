@@ -13,6 +13,7 @@ pub mod sessions;
 pub mod users;
 
 mod dto;
+mod jwt;
 
 #[cfg(feature = "rocket_support")]
 pub mod rocket;
@@ -56,7 +57,7 @@ mod tests {
             a_timestamp: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Nanos, true),
         };
 
-        documents::write(&mut session, "tests", Some("service_test"), &obj)?;
+        documents::write(&mut session, "tests", Some("service_test"), &obj, None)?;
 
         let read: DemoDTO = documents::read(&mut session, "tests", "service_test")?;
 
@@ -107,7 +108,7 @@ mod tests {
         assert_eq!(user_session.projectid, cred.project_id);
 
         let mut user_session =
-            sessions::user::Session::by_access_token(&cred, &user_session.bearer)?;
+            sessions::user::Session::by_access_token(&cred, &user_session.bearer())?;
 
         assert_eq!(user_session.userid, test_user_id);
 
@@ -118,7 +119,7 @@ mod tests {
         };
 
         // Test writing
-        let result = documents::write(&mut user_session, "tests", Some("test"), &obj)?;
+        let result = documents::write(&mut user_session, "tests", Some("test"), &obj, None)?;
         assert_eq!(result.document_id, "test");
         let duration = chrono::Utc::now().signed_duration_since(result.update_time.unwrap());
         assert!(
@@ -160,10 +161,10 @@ mod tests {
         assert_eq!(count, 2);
 
         // test if the call fails for a non existing document
-        let r = documents::delete(&mut user_session, "tests/non_existing", true);
+        let r = documents::delete(&mut user_session, "tests/non_existing", true, None);
         assert!(r.is_err());
 
-        documents::delete(&mut user_session, "tests/test", false)?;
+        documents::delete(&mut user_session, "tests/test", false, None)?;
 
         // Check if document is indeed removed
         let results: Vec<DemoDTO> = documents::query(
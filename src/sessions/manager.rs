@@ -0,0 +1,175 @@
+//! A bounded, multi-user cache of [`crate::sessions::user::Session`]s, for
+//! servers that authenticate many different end users (e.g. behind a Rocket
+//! request guard) and would otherwise pay for an OAuth round-trip on every
+//! incoming request.
+
+use crate::credentials::Credentials;
+use crate::errors::Result;
+use crate::sessions::user;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+/// Number of live sessions kept around before the least-recently-used one is
+/// evicted.
+const DEFAULT_CAPACITY: usize = 128;
+
+/// Caches [`user::Session`]s keyed by user id, evicting the
+/// least-recently-used entry once `capacity` is exceeded.
+///
+/// Safe to share across threads/requests behind an `Arc`.
+pub struct SessionManager {
+    capacity: usize,
+    inner: Mutex<Inner<Arc<user::Session>>>,
+}
+
+/// The LRU bookkeeping itself, kept generic over the cached value so its
+/// eviction/touch logic can be exercised in tests without a real, network-backed
+/// [`user::Session`].
+struct Inner<V> {
+    sessions: HashMap<String, V>,
+    /// Least-recently-used id at the front, most-recently-used at the back.
+    order: VecDeque<String>,
+}
+
+impl SessionManager {
+    /// Create a manager holding at most `capacity` live sessions.
+    pub fn new(capacity: usize) -> Self {
+        SessionManager {
+            capacity,
+            inner: Mutex::new(Inner {
+                sessions: HashMap::new(),
+                order: VecDeque::new(),
+            }),
+        }
+    }
+
+    /// Return the cached session for `user_id` if its token still has life
+    /// remaining, otherwise impersonate the user again and cache the result.
+    pub fn get_or_create(
+        &self,
+        credentials: &Credentials,
+        user_id: &str,
+    ) -> Result<Arc<user::Session>> {
+        {
+            let mut inner = self.inner.lock().unwrap();
+            if let Some(session) = inner.sessions.get(user_id) {
+                if !session.is_expired() {
+                    let session = Arc::clone(session);
+                    inner.touch(user_id);
+                    return Ok(session);
+                }
+            }
+        }
+
+        let session = Arc::new(user::Session::by_user_id(credentials, user_id)?);
+        self.inner
+            .lock()
+            .unwrap()
+            .insert(user_id.to_owned(), Arc::clone(&session), self.capacity);
+        Ok(session)
+    }
+
+    /// Exchange `refresh_token` for a session and cache it under the
+    /// resulting user id, evicting the least-recently-used entry if full.
+    pub fn get_by_refresh_token(
+        &self,
+        credentials: &Credentials,
+        refresh_token: &str,
+    ) -> Result<Arc<user::Session>> {
+        let session = Arc::new(user::Session::by_refresh_token(credentials, refresh_token)?);
+        self.inner.lock().unwrap().insert(
+            session.userid.clone(),
+            Arc::clone(&session),
+            self.capacity,
+        );
+        Ok(session)
+    }
+}
+
+impl Default for SessionManager {
+    fn default() -> Self {
+        SessionManager::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl<V> Inner<V> {
+    fn touch(&mut self, user_id: &str) {
+        if let Some(pos) = self.order.iter().position(|id| id == user_id) {
+            let id = self.order.remove(pos).unwrap();
+            self.order.push_back(id);
+        }
+    }
+
+    fn insert(&mut self, user_id: String, session: V, capacity: usize) {
+        if self.sessions.contains_key(&user_id) {
+            self.touch(&user_id);
+        } else {
+            self.order.push_back(user_id.clone());
+        }
+        self.sessions.insert(user_id, session);
+
+        while self.sessions.len() > capacity {
+            match self.order.pop_front() {
+                Some(oldest) => {
+                    self.sessions.remove(&oldest);
+                }
+                None => break,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_inner() -> Inner<i32> {
+        Inner {
+            sessions: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    #[test]
+    fn insert_beyond_capacity_evicts_least_recently_used() {
+        let mut inner = new_inner();
+        inner.insert("a".to_owned(), 1, 2);
+        inner.insert("b".to_owned(), 2, 2);
+        inner.insert("c".to_owned(), 3, 2);
+
+        assert_eq!(inner.sessions.len(), 2);
+        assert!(!inner.sessions.contains_key("a"));
+        assert!(inner.sessions.contains_key("b"));
+        assert!(inner.sessions.contains_key("c"));
+    }
+
+    #[test]
+    fn touch_moves_entry_to_most_recently_used() {
+        let mut inner = new_inner();
+        inner.insert("a".to_owned(), 1, 2);
+        inner.insert("b".to_owned(), 2, 2);
+
+        // Without touching "a", the next insert should evict it (oldest).
+        inner.touch("a");
+        inner.insert("c".to_owned(), 3, 2);
+
+        assert!(inner.sessions.contains_key("a"));
+        assert!(!inner.sessions.contains_key("b"));
+        assert!(inner.sessions.contains_key("c"));
+    }
+
+    #[test]
+    fn re_inserting_an_existing_key_counts_as_a_touch() {
+        let mut inner = new_inner();
+        inner.insert("a".to_owned(), 1, 2);
+        inner.insert("b".to_owned(), 2, 2);
+
+        // Re-inserting "a" should refresh its position, so "b" is evicted next.
+        inner.insert("a".to_owned(), 10, 2);
+        inner.insert("c".to_owned(), 3, 2);
+
+        assert_eq!(inner.sessions.get("a"), Some(&10));
+        assert!(!inner.sessions.contains_key("b"));
+        assert!(inner.sessions.contains_key("c"));
+    }
+}
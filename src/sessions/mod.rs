@@ -0,0 +1,78 @@
+//! Session types implementing [`crate::FirebaseAuthBearer`].
+//!
+//! [`service_account::Session`] authenticates as the service account itself,
+//! while [`user::Session`] represents an authenticated end user, obtained via
+//! a refresh token, a user id (service-account impersonation), or an
+//! existing access token.
+
+pub mod manager;
+pub mod service_account;
+pub mod user;
+
+pub use manager::SessionManager;
+
+use std::time::{Duration, SystemTime};
+
+/// Safety margin subtracted from a token's reported lifetime before it is
+/// considered due for refresh. Google's token endpoints hand out tokens valid
+/// for 3600s; refreshing a bit early avoids a request racing an expiry.
+pub(crate) const EXPIRY_PADDING: Duration = Duration::from_secs(600);
+
+/// A bearer token together with the instant it stops being valid.
+///
+/// Stored behind interior mutability inside the session types so that
+/// `bearer()` can keep its existing `&self -> String` signature while
+/// transparently refreshing an expired token underneath callers.
+#[derive(Debug, Clone)]
+pub struct OAuthToken {
+    pub token: String,
+    pub expiry_time: SystemTime,
+}
+
+impl OAuthToken {
+    /// Build a token that expires `expires_in` seconds from now, as reported
+    /// by the Google OAuth2 / Identity Toolkit token endpoints.
+    pub fn new(token: String, expires_in: u64) -> Self {
+        OAuthToken {
+            token,
+            expiry_time: SystemTime::now() + Duration::from_secs(expires_in),
+        }
+    }
+
+    /// The instant this token stops being valid.
+    pub fn expiry(&self) -> SystemTime {
+        self.expiry_time
+    }
+
+    /// True if fewer than [`EXPIRY_PADDING`] remain before [`OAuthToken::expiry`].
+    pub fn is_expired(&self) -> bool {
+        match self.expiry_time.duration_since(SystemTime::now()) {
+            Ok(remaining) => remaining < EXPIRY_PADDING,
+            Err(_) => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fresh_token_is_not_expired() {
+        let token = OAuthToken::new("abc".to_owned(), 3600);
+        assert!(!token.is_expired());
+        assert_eq!(token.expiry(), token.expiry_time);
+    }
+
+    #[test]
+    fn token_within_expiry_padding_is_expired() {
+        let token = OAuthToken::new("abc".to_owned(), 60);
+        assert!(token.is_expired());
+    }
+
+    #[test]
+    fn already_elapsed_token_is_expired() {
+        let token = OAuthToken::new("abc".to_owned(), 0);
+        assert!(token.is_expired());
+    }
+}
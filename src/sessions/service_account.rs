@@ -0,0 +1,86 @@
+//! A session authenticated as the service account itself, suitable for
+//! server-to-server and long-running cloud-function use.
+
+use crate::credentials::Credentials;
+use crate::errors::Result;
+use crate::jwt;
+use crate::sessions::OAuthToken;
+use crate::FirebaseAuthBearer;
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+/// A session that authenticates Firestore requests as the service account
+/// described by its [`Credentials`], rather than as an end user.
+///
+/// The cached token sits behind a `Mutex` rather than a `RefCell` so a
+/// `Session` stays `Send + Sync` and can be shared across threads (e.g.
+/// behind an `Arc`) the way [`crate::sessions::manager::SessionManager`]
+/// shares `user::Session`s.
+pub struct Session {
+    pub credentials: Credentials,
+    token: Mutex<OAuthToken>,
+}
+
+impl Session {
+    /// Create a new session, immediately minting a signed JWT.
+    pub fn new(credentials: Credentials) -> Result<Self> {
+        let token = mint_token(&credentials)?;
+        Ok(Session {
+            credentials,
+            token: Mutex::new(token),
+        })
+    }
+
+    /// The instant the currently cached token stops being valid.
+    pub fn expiry(&self) -> SystemTime {
+        self.token.lock().unwrap().expiry()
+    }
+
+    /// Whether the currently cached token needs refreshing.
+    pub fn is_expired(&self) -> bool {
+        self.token.lock().unwrap().is_expired()
+    }
+}
+
+impl<'a> FirebaseAuthBearer<'a> for Session {
+    fn projectid(&'a self) -> &'a str {
+        &self.credentials.project_id
+    }
+
+    fn bearer(&'a self) -> String {
+        let mut token = self.token.lock().unwrap();
+        if token.is_expired() {
+            if let Ok(fresh) = mint_token(&self.credentials) {
+                *token = fresh;
+            }
+        }
+        token.token.clone()
+    }
+}
+
+#[derive(Serialize)]
+struct Claims<'a> {
+    iss: &'a str,
+    sub: &'a str,
+    aud: &'a str,
+    iat: u64,
+    exp: u64,
+}
+
+/// Sign a fresh service-account JWT and wrap it with its expiry.
+///
+/// The JWT's `exp` claim is set one hour after `iat`, and the token is cached
+/// for that same hour.
+fn mint_token(credentials: &Credentials) -> Result<OAuthToken> {
+    let iat = jwt::unix_timestamp();
+    let claims = Claims {
+        iss: &credentials.client_email,
+        sub: &credentials.client_email,
+        aud: "https://www.googleapis.com/oauth2/v4/token",
+        iat,
+        exp: iat + 3600,
+    };
+    let token = jwt::sign_claims(credentials, &claims)?;
+    Ok(OAuthToken::new(token, 3600))
+}
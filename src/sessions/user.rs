@@ -0,0 +1,219 @@
+//! A session representing an authenticated end user, as opposed to the
+//! service account itself.
+
+use crate::credentials::Credentials;
+use crate::errors::{FirebaseError, Result};
+use crate::jwt;
+use crate::sessions::OAuthToken;
+use crate::FirebaseAuthBearer;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+/// A session authenticated as a specific end user, obtained either by
+/// impersonating a user id, by exchanging a refresh token, or by reusing an
+/// already-issued access token.
+///
+/// The cached token sits behind a `Mutex` rather than a `RefCell` so a
+/// `Session` stays `Send + Sync` and can be shared across threads (e.g.
+/// behind an `Arc`, the way [`crate::sessions::manager::SessionManager`]
+/// does). Call [`FirebaseAuthBearer::bearer`] to get the current token; once
+/// it expires, it is transparently refreshed with the session's refresh token.
+/// A session without one (as created by [`Session::by_access_token`]) keeps
+/// returning its original token, and so does a session whose refresh fails.
+pub struct Session {
+    pub userid: String,
+    pub projectid: String,
+    pub refresh_token: Option<String>,
+    credentials: Credentials,
+    token: Mutex<OAuthToken>,
+}
+
+impl Session {
+    /// Impersonate `user_id` by minting a custom token signed by the service
+    /// account and immediately exchanging it for an ID token.
+    pub fn by_user_id(credentials: &Credentials, user_id: &str) -> Result<Self> {
+        let custom_token = mint_custom_token(credentials, user_id)?;
+        let exchanged = exchange_custom_token(credentials, &custom_token)?;
+        Self::from_exchange(credentials.clone(), user_id.to_owned(), exchanged)
+    }
+
+    /// Re-create a session from a previously stored refresh token.
+    pub fn by_refresh_token(credentials: &Credentials, refresh_token: &str) -> Result<Self> {
+        let exchanged = exchange_refresh_token(credentials, refresh_token)?;
+        let userid = exchanged.user_id.clone().unwrap_or_default();
+        Self::from_exchange(credentials.clone(), userid, exchanged)
+    }
+
+    /// Re-create a session from an existing, still-valid access token.
+    pub fn by_access_token(credentials: &Credentials, access_token: &str) -> Result<Self> {
+        let info = lookup_account_info(credentials, access_token)?;
+        Ok(Session {
+            userid: info.local_id,
+            projectid: credentials.project_id.clone(),
+            refresh_token: None,
+            credentials: credentials.clone(),
+            token: Mutex::new(OAuthToken::new(access_token.to_owned(), 3600)),
+        })
+    }
+
+    fn from_exchange(
+        credentials: Credentials,
+        userid: String,
+        exchanged: TokenExchange,
+    ) -> Result<Self> {
+        let expires_in = exchanged.expires_in.parse::<u64>().unwrap_or(3600);
+        Ok(Session {
+            userid,
+            projectid: credentials.project_id.clone(),
+            refresh_token: exchanged.refresh_token,
+            credentials,
+            token: Mutex::new(OAuthToken::new(exchanged.id_token, expires_in)),
+        })
+    }
+
+    /// The instant the currently cached token stops being valid.
+    pub fn expiry(&self) -> SystemTime {
+        self.token.lock().unwrap().expiry()
+    }
+
+    /// Whether the currently cached token needs refreshing.
+    pub fn is_expired(&self) -> bool {
+        self.token.lock().unwrap().is_expired()
+    }
+}
+
+impl<'a> FirebaseAuthBearer<'a> for Session {
+    fn projectid(&'a self) -> &'a str {
+        &self.projectid
+    }
+
+    fn bearer(&'a self) -> String {
+        let mut token = self.token.lock().unwrap();
+        if token.is_expired() {
+            if let Some(refresh_token) = self.refresh_token.as_deref() {
+                if let Ok(exchanged) = exchange_refresh_token(&self.credentials, refresh_token) {
+                    let expires_in = exchanged.expires_in.parse::<u64>().unwrap_or(3600);
+                    *token = OAuthToken::new(exchanged.id_token, expires_in);
+                }
+            }
+        }
+        token.token.clone()
+    }
+}
+
+#[derive(Serialize)]
+struct CustomTokenClaims<'a> {
+    iss: &'a str,
+    sub: &'a str,
+    aud: &'a str,
+    uid: &'a str,
+    iat: u64,
+    exp: u64,
+}
+
+fn mint_custom_token(credentials: &Credentials, user_id: &str) -> Result<String> {
+    let iat = jwt::unix_timestamp();
+    let claims = CustomTokenClaims {
+        iss: &credentials.client_email,
+        sub: &credentials.client_email,
+        aud: "https://identitytoolkit.googleapis.com/google.identity.identitytoolkit.v1.IdentityToolkit",
+        uid: user_id,
+        iat,
+        exp: iat + 3600,
+    };
+    jwt::sign_claims(credentials, &claims)
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenExchange {
+    // signInWithCustomToken answers in camelCase, securetoken in snake_case.
+    #[serde(alias = "idToken")]
+    id_token: String,
+    #[serde(alias = "refreshToken")]
+    refresh_token: Option<String>,
+    #[serde(alias = "expiresIn")]
+    expires_in: String,
+    user_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AccountInfo {
+    #[serde(rename = "localId")]
+    local_id: String,
+}
+
+fn api_key(credentials: &Credentials) -> Result<&str> {
+    credentials.api_key.as_deref().ok_or_else(|| {
+        FirebaseError::Generic("Credentials are missing the Firebase Web API key".to_owned())
+    })
+}
+
+fn exchange_custom_token(credentials: &Credentials, custom_token: &str) -> Result<TokenExchange> {
+    let url = format!(
+        "https://identitytoolkit.googleapis.com/v1/accounts:signInWithCustomToken?key={}",
+        api_key(credentials)?
+    );
+    let resp = reqwest::blocking::Client::new()
+        .post(&url)
+        .json(&serde_json::json!({ "token": custom_token, "returnSecureToken": true }))
+        .send()?;
+    parse_token_exchange(resp)
+}
+
+fn exchange_refresh_token(credentials: &Credentials, refresh_token: &str) -> Result<TokenExchange> {
+    let url = format!(
+        "https://securetoken.googleapis.com/v1/token?key={}",
+        api_key(credentials)?
+    );
+    let resp = reqwest::blocking::Client::new()
+        .post(&url)
+        .form(&[
+            ("grant_type", "refresh_token"),
+            ("refresh_token", refresh_token),
+        ])
+        .send()?;
+    parse_token_exchange(resp)
+}
+
+fn parse_token_exchange(resp: reqwest::blocking::Response) -> Result<TokenExchange> {
+    let status = resp.status();
+    if !status.is_success() {
+        let body = resp.text().unwrap_or_default();
+        return Err(FirebaseError::APIError(
+            status.as_u16(),
+            body,
+            "exchanging token".to_owned(),
+        ));
+    }
+    Ok(resp.json()?)
+}
+
+fn lookup_account_info(credentials: &Credentials, access_token: &str) -> Result<AccountInfo> {
+    let url = format!(
+        "https://identitytoolkit.googleapis.com/v1/accounts:lookup?key={}",
+        api_key(credentials)?
+    );
+    let resp = reqwest::blocking::Client::new()
+        .post(&url)
+        .json(&serde_json::json!({ "idToken": access_token }))
+        .send()?;
+    let status = resp.status();
+    if !status.is_success() {
+        let body = resp.text().unwrap_or_default();
+        return Err(FirebaseError::APIError(
+            status.as_u16(),
+            body,
+            "looking up account info".to_owned(),
+        ));
+    }
+    #[derive(Deserialize)]
+    struct LookupResponse {
+        users: Vec<AccountInfo>,
+    }
+    let mut lookup: LookupResponse = resp.json()?;
+    lookup
+        .users
+        .pop()
+        .ok_or_else(|| FirebaseError::Generic("No account found for the given access token".to_owned()))
+}